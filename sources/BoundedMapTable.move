@@ -0,0 +1,190 @@
+module TicketTutorial::BoundedMapTable {
+    use Std::Option::{Self, Option};
+    use TicketTutorial::MapTable::{Self, MapTable};
+
+    /// A `MapTable` holding at most `capacity` entries. Adding to a full table evicts
+    /// the head entry and hands it back to the caller. With the LRU policy every
+    /// mutable borrow or `touch` moves the entry to the tail, so the head is always
+    /// the least recently used entry; with FIFO the head is the oldest insert.
+    struct BoundedMapTable<K: copy + store + drop, V: store> has store {
+        table: MapTable<K, V>,
+        capacity: u64,
+        policy: u8,
+    }
+
+    /// Evict in insertion order.
+    const EVICT_FIFO: u8 = 0;
+    /// Evict the least recently added or touched entry.
+    const EVICT_LRU: u8 = 1;
+
+    /// The capacity must be at least one.
+    const EINVALID_CAPACITY: u64 = 1;
+    /// There is no entry for the key.
+    const EKEY_NOT_FOUND: u64 = 2;
+    /// An entry for the key already exists.
+    const EKEY_ALREADY_EXISTS: u64 = 3;
+
+    /// Create an empty table that evicts in insertion order.
+    public fun new_fifo<K: copy + store + drop, V: store>(capacity: u64): BoundedMapTable<K, V> {
+        new(capacity, EVICT_FIFO)
+    }
+
+    /// Create an empty table that evicts the least recently used entry.
+    public fun new_lru<K: copy + store + drop, V: store>(capacity: u64): BoundedMapTable<K, V> {
+        new(capacity, EVICT_LRU)
+    }
+
+    fun new<K: copy + store + drop, V: store>(capacity: u64, policy: u8): BoundedMapTable<K, V> {
+        assert!(capacity > 0, EINVALID_CAPACITY);
+        BoundedMapTable {
+            table: MapTable::new(),
+            capacity,
+            policy,
+        }
+    }
+
+    /// Destroy a table. The table must be empty to succeed.
+    public fun destroy_empty<K: copy + store + drop, V: store>(table: BoundedMapTable<K, V>) {
+        let BoundedMapTable {table, capacity: _, policy: _} = table;
+        MapTable::destroy_empty(table);
+    }
+
+    /// Add a new entry at the tail. If the table is full the head entry is removed
+    /// first and returned, otherwise both options are `none`.
+    /// Aborts if an entry for this key already exists, before anything is evicted.
+    public fun add<K: copy + store + drop, V: store>(table: &mut BoundedMapTable<K, V>, key: K, val: V): (Option<K>, Option<V>) {
+        assert!(!MapTable::contains(&table.table, key), EKEY_ALREADY_EXISTS);
+        let evicted_key = Option::none();
+        let evicted_val = Option::none();
+        if (MapTable::length(&table.table) >= table.capacity) {
+            let (k, v) = MapTable::pop_front(&mut table.table);
+            Option::fill(&mut evicted_key, k);
+            Option::fill(&mut evicted_val, v);
+        };
+        MapTable::add(&mut table.table, key, val);
+        (evicted_key, evicted_val)
+    }
+
+    /// Remove from `table` and return the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun remove<K: copy + store + drop, V: store>(table: &mut BoundedMapTable<K, V>, key: K): V {
+        assert!(MapTable::contains(&table.table, key), EKEY_NOT_FOUND);
+        MapTable::remove(&mut table.table, key)
+    }
+
+    /// Acquire an immutable reference to the value which `key` maps to. This does not
+    /// count as a use; call `touch` to refresh the entry under LRU.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow<K: copy + store + drop, V: store>(table: &BoundedMapTable<K, V>, key: K): &V {
+        assert!(MapTable::contains(&table.table, key), EKEY_NOT_FOUND);
+        MapTable::borrow(&table.table, key)
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to, moving the entry
+    /// to the tail under LRU.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_mut<K: copy + store + drop, V: store>(table: &mut BoundedMapTable<K, V>, key: K): &mut V {
+        assert!(MapTable::contains(&table.table, key), EKEY_NOT_FOUND);
+        touch(table, key);
+        MapTable::borrow_mut(&mut table.table, key)
+    }
+
+    /// Mark `key` as used, moving it to the tail under LRU. No-op under FIFO.
+    /// Aborts if there is no entry for `key`.
+    public fun touch<K: copy + store + drop, V: store>(table: &mut BoundedMapTable<K, V>, key: K) {
+        assert!(MapTable::contains(&table.table, key), EKEY_NOT_FOUND);
+        if (table.policy == EVICT_LRU) {
+            MapTable::move_to_back(&mut table.table, key);
+        }
+    }
+
+    /// Returns true iff `table` contains an entry for `key`.
+    public fun contains<K: copy + store + drop, V: store>(table: &BoundedMapTable<K, V>, key: K): bool {
+        MapTable::contains(&table.table, key)
+    }
+
+    /// Returns the number of entries.
+    public fun length<K: copy + store + drop, V: store>(table: &BoundedMapTable<K, V>): u64 {
+        MapTable::length(&table.table)
+    }
+
+    /// Returns the maximum number of entries.
+    public fun capacity<K: copy + store + drop, V: store>(table: &BoundedMapTable<K, V>): u64 {
+        table.capacity
+    }
+
+    /// Returns the underlying `MapTable` for iteration and other read-only access.
+    public fun as_map_table<K: copy + store + drop, V: store>(table: &BoundedMapTable<K, V>): &MapTable<K, V> {
+        &table.table
+    }
+
+    #[test]
+    fun fifo_test() {
+        let table = new_fifo(2);
+        let (k, v) = add(&mut table, 1, 10);
+        assert!(Option::is_none(&k) && Option::is_none(&v), 0);
+        let (_, _) = add(&mut table, 2, 20);
+        *borrow_mut(&mut table, 1) = 11;
+        let (k, v) = add(&mut table, 3, 30);
+        assert!(k == Option::some(1) && v == Option::some(11), 0);
+        assert!(length(&table) == 2 && !contains(&table, 1), 0);
+        MapTable::check_invariants(as_map_table(&table));
+        remove(&mut table, 2);
+        remove(&mut table, 3);
+        destroy_empty(table);
+    }
+
+    #[test]
+    fun lru_test() {
+        let table = new_lru(3);
+        let (_, _) = add(&mut table, 1, 10);
+        let (_, _) = add(&mut table, 2, 20);
+        let (_, _) = add(&mut table, 3, 30);
+        touch(&mut table, 1);
+        *borrow_mut(&mut table, 2) = 21;
+        let (k, v) = add(&mut table, 4, 40);
+        assert!(k == Option::some(3) && v == Option::some(30), 0);
+        let (k, _) = add(&mut table, 5, 50);
+        assert!(k == Option::some(1), 0);
+        assert!(MapTable::head_key(as_map_table(&table)) == Option::some(2), 0);
+        MapTable::check_invariants(as_map_table(&table));
+        remove(&mut table, 2);
+        remove(&mut table, 4);
+        remove(&mut table, 5);
+        destroy_empty(table);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 1)]
+    fun zero_capacity_test() {
+        destroy_empty(new_fifo<u64, u64>(0));
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 3)]
+    fun add_head_key_when_full_test() {
+        let table = new_fifo(2);
+        let (_, _) = add(&mut table, 1, 10);
+        let (_, _) = add(&mut table, 2, 20);
+        let (_, _) = add(&mut table, 1, 11);
+        remove(&mut table, 1);
+        remove(&mut table, 2);
+        destroy_empty(table);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 2)]
+    fun lru_touch_missing_key_test() {
+        let table = new_lru<u64, u64>(2);
+        touch(&mut table, 1);
+        destroy_empty(table);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 2)]
+    fun remove_missing_key_test() {
+        let table = new_fifo<u64, u64>(2);
+        remove(&mut table, 1);
+        destroy_empty(table);
+    }
+}
@@ -0,0 +1,268 @@
+module TicketTutorial::MapSet {
+    use Std::Option::{Self, Option};
+    use AptosFramework::Table::{Self, Table};
+    #[test_only]
+    use Std::Vector;
+
+    /// The iterable wrapper around a member, points to previous and next key if any.
+    struct SetNode<K: copy + store + drop> has store, drop {
+        prev: Option<K>,
+        next: Option<K>,
+    }
+
+    /// An iterable set implementation based on double linked list, the same layout
+    /// as `MapTable` without a value per entry. It keeps no `keys` vector or slot
+    /// index either, so each member stores only its two links.
+    struct MapSet<K: copy + store + drop> has store {
+        inner: Table<K, SetNode<K>>,
+        head: Option<K>,
+        tail: Option<K>,
+    }
+
+    /// The set still has members.
+    const ESET_NOT_EMPTY: u64 = 1;
+    /// The key is already a member.
+    const EKEY_ALREADY_EXISTS: u64 = 2;
+    /// The key is not a member.
+    const EKEY_NOT_FOUND: u64 = 3;
+    /// The linked list and the inner table disagree.
+    const EINVARIANT_VIOLATED: u64 = 4;
+
+    /// Create an empty set.
+    public fun new<K: copy + store + drop>(): MapSet<K> {
+        MapSet {
+            inner: Table::new(),
+            head: Option::none(),
+            tail: Option::none(),
+        }
+    }
+
+    /// Destroy a set. The set must be empty to succeed.
+    public fun destroy_empty<K: copy + store + drop>(set: MapSet<K>) {
+        assert!(empty(&set), ESET_NOT_EMPTY);
+        let MapSet {inner, head: _, tail: _} = set;
+        Table::destroy_empty(inner);
+    }
+
+    /// Remove every member and destroy the set.
+    public fun destroy<K: copy + store + drop>(set: MapSet<K>) {
+        while (Option::is_some(&set.head)) {
+            let key = *Option::borrow(&set.head);
+            remove(&mut set, key);
+        };
+        destroy_empty(set);
+    }
+
+    /// Add `key` at the tail of the set. Aborts if `key` is already a member.
+    public fun add<K: copy + store + drop>(set: &mut MapSet<K>, key: K) {
+        assert!(!Table::contains(&set.inner, key), EKEY_ALREADY_EXISTS);
+        let node = SetNode {
+            prev: set.tail,
+            next: Option::none(),
+        };
+        Table::add(&mut set.inner, key, node);
+        if (Option::is_some(&set.tail)) {
+            let k = Option::borrow(&set.tail);
+            Table::borrow_mut(&mut set.inner, *k).next = Option::some(key);
+        } else {
+            set.head = Option::some(key);
+        };
+        set.tail = Option::some(key);
+    }
+
+    /// Remove `key` from the set. Aborts if `key` is not a member.
+    public fun remove<K: copy + store + drop>(set: &mut MapSet<K>, key: K) {
+        assert!(Table::contains(&set.inner, key), EKEY_NOT_FOUND);
+        let SetNode {prev, next} = Table::remove(&mut set.inner, key);
+        if (Option::is_some(&prev)) {
+            let k = Option::borrow(&prev);
+            Table::borrow_mut(&mut set.inner, *k).next = next;
+        } else {
+            set.head = next;
+        };
+        if (Option::is_some(&next)) {
+            let k = Option::borrow(&next);
+            Table::borrow_mut(&mut set.inner, *k).prev = prev;
+        } else {
+            set.tail = prev;
+        };
+    }
+
+    /// Returns true iff `key` is a member of `set`.
+    public fun contains<K: copy + store + drop>(set: &MapSet<K>, key: K): bool {
+        Table::contains(&set.inner, key)
+    }
+
+    /// Returns the number of members.
+    public fun length<K: copy + store + drop>(set: &MapSet<K>): u64 {
+        Table::length(&set.inner)
+    }
+
+    /// Returns true if this set is empty.
+    public fun empty<K: copy + store + drop>(set: &MapSet<K>): bool {
+        Table::empty(&set.inner)
+    }
+
+    /// Walk the whole list and abort with `EINVARIANT_VIOLATED` unless `head`/`tail`,
+    /// every `prev`/`next` link and the set length all agree. This is O(n) and meant
+    /// for tests and debugging, not regular calls.
+    public fun check_invariants<K: copy + store + drop>(set: &MapSet<K>) {
+        let len = Table::length(&set.inner);
+        assert!(Option::is_none(&set.head) == (len == 0), EINVARIANT_VIOLATED);
+        assert!(Option::is_none(&set.tail) == (len == 0), EINVARIANT_VIOLATED);
+        let count = 0;
+        let prev = Option::none<K>();
+        let key = set.head;
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            assert!(Table::contains(&set.inner, k), EINVARIANT_VIOLATED);
+            let node = Table::borrow(&set.inner, k);
+            assert!(node.prev == prev, EINVARIANT_VIOLATED);
+            // A cycle would revisit members, so stop once we pass the length.
+            count = count + 1;
+            assert!(count <= len, EINVARIANT_VIOLATED);
+            prev = key;
+            key = node.next;
+        };
+        assert!(prev == set.tail, EINVARIANT_VIOLATED);
+        assert!(count == len, EINVARIANT_VIOLATED);
+    }
+
+    /// Iteration API.
+
+    /// Returns the key of the head for iteration.
+    public fun head_key<K: copy + store + drop>(set: &MapSet<K>): Option<K> {
+        set.head
+    }
+
+    /// Returns the key of the tail for iteration.
+    public fun tail_key<K: copy + store + drop>(set: &MapSet<K>): Option<K> {
+        set.tail
+    }
+
+    /// Returns the previous and next member around `key`.
+    /// Aborts if `key` is not a member.
+    public fun borrow_iter<K: copy + store + drop>(set: &MapSet<K>, key: K): (Option<K>, Option<K>) {
+        assert!(Table::contains(&set.inner, key), EKEY_NOT_FOUND);
+        let node = Table::borrow(&set.inner, key);
+        (node.prev, node.next)
+    }
+
+    /// Set algebra. Each result is a new set ordered by `a` first, then `b`.
+
+    /// Returns every member of `a` followed by the members of `b` not in `a`.
+    public fun union<K: copy + store + drop>(a: &MapSet<K>, b: &MapSet<K>): MapSet<K> {
+        let result = new();
+        let key = a.head;
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            add(&mut result, k);
+            key = Table::borrow(&a.inner, k).next;
+        };
+        let key = b.head;
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            if (!contains(&result, k)) add(&mut result, k);
+            key = Table::borrow(&b.inner, k).next;
+        };
+        result
+    }
+
+    /// Returns the members of `a` that are also in `b`.
+    public fun intersect<K: copy + store + drop>(a: &MapSet<K>, b: &MapSet<K>): MapSet<K> {
+        filter(a, b, true)
+    }
+
+    /// Returns the members of `a` that are not in `b`.
+    public fun difference<K: copy + store + drop>(a: &MapSet<K>, b: &MapSet<K>): MapSet<K> {
+        filter(a, b, false)
+    }
+
+    /// Collect the members of `a` whose membership in `b` equals `in_b`.
+    fun filter<K: copy + store + drop>(a: &MapSet<K>, b: &MapSet<K>, in_b: bool): MapSet<K> {
+        let result = new();
+        let key = a.head;
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            if (contains(b, k) == in_b) add(&mut result, k);
+            key = Table::borrow(&a.inner, k).next;
+        };
+        result
+    }
+
+    #[test_only]
+    /// Collect the members in list order.
+    fun collect_keys<K: copy + store + drop>(set: &MapSet<K>): vector<K> {
+        let keys = Vector::empty();
+        let key = head_key(set);
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            Vector::push_back(&mut keys, k);
+            let (_, next) = borrow_iter(set, k);
+            key = next;
+        };
+        keys
+    }
+
+    #[test]
+    fun Map_set_test() {
+        let set = new();
+        let i = 0;
+        while (i < 10) {
+            add(&mut set, i);
+            check_invariants(&set);
+            i = i + 1;
+        };
+        assert!(length(&set) == 10, 0);
+        remove(&mut set, 0);
+        check_invariants(&set);
+        remove(&mut set, 5);
+        check_invariants(&set);
+        remove(&mut set, 9);
+        check_invariants(&set);
+        assert!(!contains(&set, 5) && contains(&set, 6), 0);
+        assert!(head_key(&set) == Option::some(1), 0);
+        assert!(tail_key(&set) == Option::some(8), 0);
+        assert!(collect_keys(&set) == vector[1, 2, 3, 4, 6, 7, 8], 0);
+        let (prev, next) = borrow_iter(&set, 6);
+        assert!(prev == Option::some(4) && next == Option::some(7), 0);
+        destroy(set);
+    }
+
+    #[test]
+    fun set_algebra_test() {
+        let a = new();
+        let b = new();
+        add(&mut a, 3);
+        add(&mut a, 1);
+        add(&mut a, 2);
+        add(&mut b, 4);
+        add(&mut b, 2);
+        add(&mut b, 3);
+        let u = union(&a, &b);
+        let i = intersect(&a, &b);
+        let d = difference(&a, &b);
+        check_invariants(&a);
+        check_invariants(&b);
+        check_invariants(&u);
+        check_invariants(&i);
+        check_invariants(&d);
+        assert!(collect_keys(&u) == vector[3, 1, 2, 4], 0);
+        assert!(collect_keys(&i) == vector[3, 2], 0);
+        assert!(collect_keys(&d) == vector[1], 0);
+        destroy(a);
+        destroy(b);
+        destroy(u);
+        destroy(i);
+        destroy(d);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 2)]
+    fun add_duplicate_test() {
+        let set = new();
+        add(&mut set, 1);
+        add(&mut set, 1);
+        destroy(set);
+    }
+}
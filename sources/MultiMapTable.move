@@ -0,0 +1,284 @@
+module TicketTutorial::MultiMapTable {
+    use Std::Option::{Self, Option};
+    use Std::Vector;
+    use AptosFramework::Table::{Self, Table};
+    use TicketTutorial::MapTable::{Self, MapTable};
+
+    /// Identifies one value: the key it belongs to plus a sequence number unique
+    /// within the table.
+    struct ValueId<K: copy + store + drop> has copy, drop, store {
+        key: K,
+        seq: u64,
+    }
+
+    /// A value with the sequence numbers of its neighbours under the same key.
+    struct KeyedValue<V: store> has store {
+        val: V,
+        key_prev: Option<u64>,
+        key_next: Option<u64>,
+    }
+
+    /// Ends and size of the value list for one key.
+    struct KeyList has store, drop {
+        head: Option<u64>,
+        tail: Option<u64>,
+        count: u64,
+    }
+
+    /// A map from each key to an ordered list of values. All values also form one
+    /// list in insertion order, so the table can be walked either way.
+    struct MultiMapTable<K: copy + store + drop, V: store> has store {
+        values: MapTable<ValueId<K>, KeyedValue<V>>,
+        lists: Table<K, KeyList>,
+        next_seq: u64,
+    }
+
+    /// The table still has values.
+    const ETABLE_NOT_EMPTY: u64 = 1;
+    /// The key has no values.
+    const EKEY_NOT_FOUND: u64 = 2;
+
+    /// Create an empty table.
+    public fun new<K: copy + store + drop, V: store>(): MultiMapTable<K, V> {
+        MultiMapTable {
+            values: MapTable::new(),
+            lists: Table::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Destroy a table. The table must be empty to succeed.
+    public fun destroy_empty<K: copy + store + drop, V: store>(table: MultiMapTable<K, V>) {
+        assert!(empty(&table), ETABLE_NOT_EMPTY);
+        let MultiMapTable {values, lists, next_seq: _} = table;
+        MapTable::destroy_empty(values);
+        Table::destroy_empty(lists);
+    }
+
+    /// Append `val` to the values of `key` and return its id.
+    public fun add<K: copy + store + drop, V: store>(table: &mut MultiMapTable<K, V>, key: K, val: V): ValueId<K> {
+        let seq = table.next_seq;
+        table.next_seq = seq + 1;
+        if (!Table::contains(&table.lists, key)) {
+            Table::add(&mut table.lists, key, KeyList {head: Option::none(), tail: Option::none(), count: 0});
+        };
+        let list = Table::borrow_mut(&mut table.lists, key);
+        if (Option::is_some(&list.tail)) {
+            let tail_id = ValueId {key, seq: *Option::borrow(&list.tail)};
+            MapTable::borrow_mut(&mut table.values, tail_id).key_next = Option::some(seq);
+        } else {
+            list.head = Option::some(seq);
+        };
+        let wrapped_value = KeyedValue {
+            val,
+            key_prev: list.tail,
+            key_next: Option::none(),
+        };
+        list.tail = Option::some(seq);
+        list.count = list.count + 1;
+        let id = ValueId {key, seq};
+        MapTable::add(&mut table.values, id, wrapped_value);
+        id
+    }
+
+    /// Remove and return the value `id` refers to.
+    /// Aborts if there is no such value.
+    public fun remove<K: copy + store + drop, V: store>(table: &mut MultiMapTable<K, V>, id: ValueId<K>): V {
+        let KeyedValue {val, key_prev, key_next} = MapTable::remove(&mut table.values, id);
+        let key = id.key;
+        let list = Table::borrow_mut(&mut table.lists, key);
+        if (Option::is_some(&key_prev)) {
+            let prev_id = ValueId {key, seq: *Option::borrow(&key_prev)};
+            MapTable::borrow_mut(&mut table.values, prev_id).key_next = key_next;
+        } else {
+            list.head = key_next;
+        };
+        if (Option::is_some(&key_next)) {
+            let next_id = ValueId {key, seq: *Option::borrow(&key_next)};
+            MapTable::borrow_mut(&mut table.values, next_id).key_prev = key_prev;
+        } else {
+            list.tail = key_prev;
+        };
+        list.count = list.count - 1;
+        if (list.count == 0) {
+            Table::remove(&mut table.lists, key);
+        };
+        val
+    }
+
+    /// Remove and return the oldest value of `key`.
+    /// Aborts if `key` has no values.
+    public fun remove_one<K: copy + store + drop, V: store>(table: &mut MultiMapTable<K, V>, key: K): V {
+        assert!(Table::contains(&table.lists, key), EKEY_NOT_FOUND);
+        let seq = *Option::borrow(&Table::borrow(&table.lists, key).head);
+        remove(table, ValueId {key, seq})
+    }
+
+    /// Remove and return every value of `key` in order. Returns an empty vector if
+    /// `key` has no values.
+    public fun remove_all_for_key<K: copy + store + drop, V: store>(table: &mut MultiMapTable<K, V>, key: K): vector<V> {
+        let vals = Vector::empty();
+        while (Table::contains(&table.lists, key)) {
+            Vector::push_back(&mut vals, remove_one(table, key));
+        };
+        vals
+    }
+
+    /// Returns the number of values stored for `key`.
+    public fun count_for_key<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, key: K): u64 {
+        if (Table::contains(&table.lists, key)) {
+            Table::borrow(&table.lists, key).count
+        } else {
+            0
+        }
+    }
+
+    /// Returns true iff `key` has at least one value.
+    public fun contains_key<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, key: K): bool {
+        Table::contains(&table.lists, key)
+    }
+
+    /// Returns the total number of values.
+    public fun length<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>): u64 {
+        MapTable::length(&table.values)
+    }
+
+    /// Returns true if this table has no values.
+    public fun empty<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>): bool {
+        MapTable::empty(&table.values)
+    }
+
+    /// Acquire an immutable reference to the value `id` refers to.
+    /// Aborts if there is no such value.
+    public fun borrow<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, id: ValueId<K>): &V {
+        &MapTable::borrow(&table.values, id).val
+    }
+
+    /// Acquire a mutable reference to the value `id` refers to.
+    /// Aborts if there is no such value.
+    public fun borrow_mut<K: copy + store + drop, V: store>(table: &mut MultiMapTable<K, V>, id: ValueId<K>): &mut V {
+        &mut MapTable::borrow_mut(&mut table.values, id).val
+    }
+
+    /// Returns the key a value id belongs to.
+    public fun id_key<K: copy + store + drop>(id: &ValueId<K>): K {
+        id.key
+    }
+
+    /// Iteration across all values in insertion order.
+
+    /// Returns the id of the oldest value in the table.
+    public fun head_id<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>): Option<ValueId<K>> {
+        MapTable::head_key(&table.values)
+    }
+
+    /// Returns the id of the newest value in the table.
+    public fun tail_id<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>): Option<ValueId<K>> {
+        MapTable::tail_key(&table.values)
+    }
+
+    /// Acquire an immutable reference to the value `id` refers to and the ids of the
+    /// values added just before and after it, under any key.
+    /// Aborts if there is no such value.
+    public fun borrow_iter<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, id: ValueId<K>): (&V, Option<ValueId<K>>, Option<ValueId<K>>) {
+        let (v, prev, next) = MapTable::borrow_iter(&table.values, id);
+        (&v.val, prev, next)
+    }
+
+    /// Iteration over the values of a single key.
+
+    /// Returns the id of the oldest value of `key`, if any.
+    public fun key_head_id<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, key: K): Option<ValueId<K>> {
+        if (!Table::contains(&table.lists, key)) return Option::none();
+        let seq = *Option::borrow(&Table::borrow(&table.lists, key).head);
+        Option::some(ValueId {key, seq})
+    }
+
+    /// Returns the id of the newest value of `key`, if any.
+    public fun key_tail_id<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, key: K): Option<ValueId<K>> {
+        if (!Table::contains(&table.lists, key)) return Option::none();
+        let seq = *Option::borrow(&Table::borrow(&table.lists, key).tail);
+        Option::some(ValueId {key, seq})
+    }
+
+    /// Acquire an immutable reference to the value `id` refers to and the ids of the
+    /// previous and next values under the same key.
+    /// Aborts if there is no such value.
+    public fun borrow_key_iter<K: copy + store + drop, V: store>(table: &MultiMapTable<K, V>, id: ValueId<K>): (&V, Option<ValueId<K>>, Option<ValueId<K>>) {
+        let v = MapTable::borrow(&table.values, id);
+        (&v.val, to_id(id.key, v.key_prev), to_id(id.key, v.key_next))
+    }
+
+    /// Turn a sequence number under `key` into a value id.
+    fun to_id<K: copy + store + drop>(key: K, seq: Option<u64>): Option<ValueId<K>> {
+        if (Option::is_some(&seq)) {
+            Option::some(ValueId {key, seq: *Option::borrow(&seq)})
+        } else {
+            Option::none()
+        }
+    }
+
+    #[test_only]
+    /// Collect the values of `key` by walking its own list.
+    fun collect_for_key(table: &MultiMapTable<u64, u64>, key: u64): vector<u64> {
+        let vals = Vector::empty();
+        let id = key_head_id(table, key);
+        while (Option::is_some(&id)) {
+            let (v, _, next) = borrow_key_iter(table, *Option::borrow(&id));
+            Vector::push_back(&mut vals, *v);
+            id = next;
+        };
+        vals
+    }
+
+    #[test_only]
+    /// Collect all values in insertion order.
+    fun collect_all(table: &MultiMapTable<u64, u64>): vector<u64> {
+        let vals = Vector::empty();
+        let id = head_id(table);
+        while (Option::is_some(&id)) {
+            let (v, _, next) = borrow_iter(table, *Option::borrow(&id));
+            Vector::push_back(&mut vals, *v);
+            id = next;
+        };
+        vals
+    }
+
+    #[test]
+    fun Multi_map_table_test() {
+        let table = new();
+        add(&mut table, 1, 10);
+        add(&mut table, 2, 20);
+        let middle = add(&mut table, 1, 11);
+        add(&mut table, 2, 21);
+        add(&mut table, 1, 12);
+        assert!(length(&table) == 5, 0);
+        assert!(count_for_key(&table, 1) == 3, 0);
+        assert!(count_for_key(&table, 3) == 0, 0);
+        assert!(collect_all(&table) == vector[10, 20, 11, 21, 12], 0);
+        assert!(collect_for_key(&table, 1) == vector[10, 11, 12], 0);
+        assert!(id_key(&middle) == 1, 0);
+
+        assert!(remove(&mut table, middle) == 11, 0);
+        assert!(collect_for_key(&table, 1) == vector[10, 12], 0);
+        assert!(remove_one(&mut table, 1) == 10, 0);
+        assert!(key_tail_id(&table, 1) == key_head_id(&table, 1), 0);
+        let only = *Option::borrow(&key_head_id(&table, 1));
+        *borrow_mut(&mut table, only) = 13;
+        assert!(collect_all(&table) == vector[20, 21, 13], 0);
+
+        assert!(remove_all_for_key(&mut table, 2) == vector[20, 21], 0);
+        assert!(!contains_key(&table, 2), 0);
+        assert!(remove_all_for_key(&mut table, 1) == vector[13], 0);
+        assert!(Option::is_none(&key_head_id(&table, 1)), 0);
+        destroy_empty(table);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 2)]
+    fun remove_one_missing_key_test() {
+        let table = new<u64, u64>();
+        remove_one(&mut table, 1);
+        destroy_empty(table);
+    }
+}
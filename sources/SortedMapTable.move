@@ -0,0 +1,427 @@
+module TicketTutorial::SortedMapTable {
+    use Std::ASCII;
+    use Std::Hash;
+    use Std::Option::{Self, Option};
+    use Std::Vector;
+    use AptosFramework::Table::{Self, Table};
+
+    /// A value with its level 0 back link and one forward link per level it is on.
+    struct Node<V: store> has store {
+        val: V,
+        prev: Option<vector<u8>>,
+        nexts: vector<Option<vector<u8>>>,
+    }
+
+    /// A table kept in ascending key order, laid out as a skip list over `Table`.
+    /// Keys are byte strings compared lexicographically; use `u64_key` and
+    /// `string_key` to encode integers and ASCII strings so that byte order matches
+    /// their natural order. Each node's level comes from hashing its key together with
+    /// a per-table insertion counter, so add, remove and lookups take O(log n) expected
+    /// steps. There is no on-chain randomness: the counter is public state, so a caller
+    /// who controls every key and the order of inserts can still push most nodes onto
+    /// level 1 and make operations linear. Only use it where keys come from trusted
+    /// code or where an adversary cannot time their inserts.
+    struct SortedMapTable<V: store> has store {
+        inner: Table<vector<u8>, Node<V>>,
+        heads: vector<Option<vector<u8>>>,
+        tail: Option<vector<u8>>,
+        // Mixed into the level hash and bumped on every add, so a key's level is not
+        // a fixed function of the key.
+        salt: u64,
+    }
+
+    /// Number of levels in the skip list, enough for far more entries than a table
+    /// will hold with one node in four promoted per level.
+    const MAX_LEVEL: u64 = 16;
+
+    /// The table still has entries.
+    const ETABLE_NOT_EMPTY: u64 = 1;
+    /// An entry for the key already exists.
+    const EKEY_ALREADY_EXISTS: u64 = 2;
+    /// There is no entry for the key.
+    const EKEY_NOT_FOUND: u64 = 3;
+    /// The key is not an 8 byte `u64` encoding.
+    const EINVALID_U64_KEY: u64 = 4;
+    /// The linked levels and the inner table disagree.
+    const EINVARIANT_VIOLATED: u64 = 5;
+
+    /// Create an empty table.
+    public fun new<V: store>(): SortedMapTable<V> {
+        let heads = Vector::empty();
+        let i = 0;
+        while (i < MAX_LEVEL) {
+            Vector::push_back(&mut heads, Option::none());
+            i = i + 1;
+        };
+        SortedMapTable {
+            inner: Table::new(),
+            heads,
+            tail: Option::none(),
+            salt: 0,
+        }
+    }
+
+    /// Destroy a table. The table must be empty to succeed.
+    public fun destroy_empty<V: store>(table: SortedMapTable<V>) {
+        assert!(empty(&table), ETABLE_NOT_EMPTY);
+        let SortedMapTable {inner, heads: _, tail: _, salt: _} = table;
+        Table::destroy_empty(inner);
+    }
+
+    /// Add a new entry in key order. Aborts if an entry for this key already exists.
+    public fun add<V: store>(table: &mut SortedMapTable<V>, key: vector<u8>, val: V) {
+        assert!(!Table::contains(&table.inner, copy key), EKEY_ALREADY_EXISTS);
+        let preds = predecessors(table, &key, false);
+        let level = node_level(&key, table.salt);
+        table.salt = table.salt + 1;
+        let nexts = Vector::empty();
+        let l = 0;
+        while (l < level) {
+            let pred = *Vector::borrow(&preds, l);
+            Vector::push_back(&mut nexts, next_of(table, &pred, l));
+            set_next(table, &pred, l, Option::some(copy key));
+            l = l + 1;
+        };
+        let next = *Vector::borrow(&nexts, 0);
+        if (Option::is_some(&next)) {
+            Table::borrow_mut(&mut table.inner, *Option::borrow(&next)).prev = Option::some(copy key);
+        } else {
+            table.tail = Option::some(copy key);
+        };
+        let node = Node {
+            val,
+            prev: *Vector::borrow(&preds, 0),
+            nexts,
+        };
+        Table::add(&mut table.inner, key, node);
+    }
+
+    /// Remove from `table` and return the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun remove<V: store>(table: &mut SortedMapTable<V>, key: vector<u8>): V {
+        assert!(Table::contains(&table.inner, copy key), EKEY_NOT_FOUND);
+        let preds = predecessors(table, &key, false);
+        let Node {val, prev, nexts} = Table::remove(&mut table.inner, key);
+        let l = 0;
+        let level = Vector::length(&nexts);
+        while (l < level) {
+            let pred = *Vector::borrow(&preds, l);
+            set_next(table, &pred, l, *Vector::borrow(&nexts, l));
+            l = l + 1;
+        };
+        let next = *Vector::borrow(&nexts, 0);
+        if (Option::is_some(&next)) {
+            Table::borrow_mut(&mut table.inner, *Option::borrow(&next)).prev = prev;
+        } else {
+            table.tail = prev;
+        };
+        val
+    }
+
+    /// Acquire an immutable reference to the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow<V: store>(table: &SortedMapTable<V>, key: vector<u8>): &V {
+        assert!(Table::contains(&table.inner, copy key), EKEY_NOT_FOUND);
+        &Table::borrow(&table.inner, key).val
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_mut<V: store>(table: &mut SortedMapTable<V>, key: vector<u8>): &mut V {
+        assert!(Table::contains(&table.inner, copy key), EKEY_NOT_FOUND);
+        &mut Table::borrow_mut(&mut table.inner, key).val
+    }
+
+    /// Returns the length of the table, i.e. the number of entries.
+    public fun length<V: store>(table: &SortedMapTable<V>): u64 {
+        Table::length(&table.inner)
+    }
+
+    /// Returns true if this table is empty.
+    public fun empty<V: store>(table: &SortedMapTable<V>): bool {
+        Table::empty(&table.inner)
+    }
+
+    /// Returns true iff `table` contains an entry for `key`.
+    public fun contains<V: store>(table: &SortedMapTable<V>, key: vector<u8>): bool {
+        Table::contains(&table.inner, key)
+    }
+
+    /// Ordered API.
+
+    /// Returns the smallest key, if any.
+    public fun min<V: store>(table: &SortedMapTable<V>): Option<vector<u8>> {
+        *Vector::borrow(&table.heads, 0)
+    }
+
+    /// Returns the largest key, if any.
+    public fun max<V: store>(table: &SortedMapTable<V>): Option<vector<u8>> {
+        table.tail
+    }
+
+    /// Returns the smallest key that is greater than or equal to `key`, if any.
+    public fun lower_bound<V: store>(table: &SortedMapTable<V>, key: vector<u8>): Option<vector<u8>> {
+        let preds = predecessors(table, &key, false);
+        next_of(table, Vector::borrow(&preds, 0), 0)
+    }
+
+    /// Returns the smallest key that is strictly greater than `key`, if any.
+    public fun upper_bound<V: store>(table: &SortedMapTable<V>, key: vector<u8>): Option<vector<u8>> {
+        let preds = predecessors(table, &key, true);
+        next_of(table, Vector::borrow(&preds, 0), 0)
+    }
+
+    /// Return up to `limit` keys in `[from, to)` in ascending order, along with the
+    /// key to pass as `from` for the next call. That key is `none` once the range is
+    /// exhausted.
+    public fun range<V: store>(table: &SortedMapTable<V>, from: vector<u8>, to: vector<u8>, limit: u64): (vector<vector<u8>>, Option<vector<u8>>) {
+        let keys = Vector::empty();
+        let key = lower_bound(table, from);
+        while (Option::is_some(&key) && less(Option::borrow(&key), &to)) {
+            if (Vector::length(&keys) == limit) return (keys, key);
+            let k = *Option::borrow(&key);
+            key = *Vector::borrow(&Table::borrow(&table.inner, copy k).nexts, 0);
+            Vector::push_back(&mut keys, k);
+        };
+        (keys, Option::none())
+    }
+
+    /// Acquire an immutable reference to the value and previous/next key in sort order
+    /// which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_iter<V: store>(table: &SortedMapTable<V>, key: vector<u8>): (&V, Option<vector<u8>>, Option<vector<u8>>) {
+        assert!(Table::contains(&table.inner, copy key), EKEY_NOT_FOUND);
+        let node = Table::borrow(&table.inner, key);
+        (&node.val, node.prev, *Vector::borrow(&node.nexts, 0))
+    }
+
+    /// Key encodings.
+
+    /// Encode `n` as 8 big-endian bytes, so byte order equals numeric order.
+    public fun u64_key(n: u64): vector<u8> {
+        let bytes = Vector::empty();
+        let i = 0;
+        while (i < 8) {
+            let shift = ((7 - i) * 8 as u8);
+            Vector::push_back(&mut bytes, (((n >> shift) & 0xff) as u8));
+            i = i + 1;
+        };
+        bytes
+    }
+
+    /// Decode a key produced by `u64_key`.
+    /// Aborts if `key` is not 8 bytes long.
+    public fun key_to_u64(key: &vector<u8>): u64 {
+        assert!(Vector::length(key) == 8, EINVALID_U64_KEY);
+        let n = 0;
+        let i = 0;
+        while (i < 8) {
+            n = (n << 8) | (*Vector::borrow(key, i) as u64);
+            i = i + 1;
+        };
+        n
+    }
+
+    /// Encode an ASCII string as its bytes, which sort lexicographically.
+    public fun string_key(s: &ASCII::String): vector<u8> {
+        *ASCII::as_bytes(s)
+    }
+
+    /// Walk every level and abort with `EINVARIANT_VIOLATED` unless each is in strictly
+    /// ascending order, level 0 holds every entry with matching back links and `tail`
+    /// is the last entry. This is O(n) and meant for tests and debugging.
+    public fun check_invariants<V: store>(table: &SortedMapTable<V>) {
+        let l = 0;
+        while (l < MAX_LEVEL) {
+            let count = 0;
+            let prev = Option::none<vector<u8>>();
+            let key = *Vector::borrow(&table.heads, l);
+            while (Option::is_some(&key)) {
+                let k = *Option::borrow(&key);
+                assert!(Table::contains(&table.inner, copy k), EINVARIANT_VIOLATED);
+                let node = Table::borrow(&table.inner, copy k);
+                assert!(Vector::length(&node.nexts) > l, EINVARIANT_VIOLATED);
+                if (Option::is_some(&prev)) {
+                    assert!(less(Option::borrow(&prev), &k), EINVARIANT_VIOLATED);
+                };
+                if (l == 0) assert!(node.prev == prev, EINVARIANT_VIOLATED);
+                count = count + 1;
+                assert!(count <= length(table), EINVARIANT_VIOLATED);
+                prev = key;
+                key = *Vector::borrow(&node.nexts, l);
+            };
+            if (l == 0) {
+                assert!(count == length(table), EINVARIANT_VIOLATED);
+                assert!(prev == table.tail, EINVARIANT_VIOLATED);
+            };
+            l = l + 1;
+        };
+    }
+
+    /// Returns, for every level, the last key strictly less than `key`, or less than
+    /// or equal to it if `inclusive`. `none` stands for the head of that level.
+    fun predecessors<V: store>(table: &SortedMapTable<V>, key: &vector<u8>, inclusive: bool): vector<Option<vector<u8>>> {
+        let preds = Vector::empty();
+        let cur = Option::none<vector<u8>>();
+        let l = MAX_LEVEL;
+        while (l > 0) {
+            l = l - 1;
+            loop {
+                let next = next_of(table, &cur, l);
+                if (Option::is_none(&next)) break;
+                let next_key = Option::borrow(&next);
+                if (!(less(next_key, key) || (inclusive && next_key == key))) break;
+                cur = next;
+            };
+            Vector::push_back(&mut preds, cur);
+        };
+        // Collected from the top level down.
+        Vector::reverse(&mut preds);
+        preds
+    }
+
+    /// Returns the key after `cur` on level `l`, where `none` is the head.
+    fun next_of<V: store>(table: &SortedMapTable<V>, cur: &Option<vector<u8>>, l: u64): Option<vector<u8>> {
+        if (Option::is_none(cur)) {
+            *Vector::borrow(&table.heads, l)
+        } else {
+            *Vector::borrow(&Table::borrow(&table.inner, *Option::borrow(cur)).nexts, l)
+        }
+    }
+
+    /// Point `cur`, or the head when `none`, at `next` on level `l`.
+    fun set_next<V: store>(table: &mut SortedMapTable<V>, cur: &Option<vector<u8>>, l: u64, next: Option<vector<u8>>) {
+        if (Option::is_none(cur)) {
+            *Vector::borrow_mut(&mut table.heads, l) = next;
+        } else {
+            let node = Table::borrow_mut(&mut table.inner, *Option::borrow(cur));
+            *Vector::borrow_mut(&mut node.nexts, l) = next;
+        }
+    }
+
+    /// Number of levels for `key`: one, plus one more for each leading byte of the
+    /// hash of `key` and `salt` below 64, which happens with probability 1/4.
+    fun node_level(key: &vector<u8>, salt: u64): u64 {
+        let seed = *key;
+        Vector::append(&mut seed, u64_key(salt));
+        let hash = Hash::sha3_256(seed);
+        let level = 1;
+        while (level < MAX_LEVEL && *Vector::borrow(&hash, level - 1) < 64) {
+            level = level + 1;
+        };
+        level
+    }
+
+    /// Lexicographic byte comparison; a proper prefix sorts first.
+    fun less(a: &vector<u8>, b: &vector<u8>): bool {
+        let len_a = Vector::length(a);
+        let len_b = Vector::length(b);
+        let i = 0;
+        while (i < len_a && i < len_b) {
+            let x = *Vector::borrow(a, i);
+            let y = *Vector::borrow(b, i);
+            if (x != y) return x < y;
+            i = i + 1;
+        };
+        len_a < len_b
+    }
+
+    #[test_only]
+    fun destroy_table(table: SortedMapTable<u64>) {
+        while (!empty(&table)) {
+            let k = *Option::borrow(&min(&table));
+            remove(&mut table, k);
+        };
+        destroy_empty(table);
+    }
+
+    #[test]
+    fun Sorted_map_table_test() {
+        let table = new();
+        // Insert 0..100 in a scrambled order: 37 is coprime with 100.
+        let i = 0;
+        while (i < 100) {
+            let n = (i * 37) % 100;
+            add(&mut table, u64_key(n), n);
+            i = i + 1;
+        };
+        check_invariants(&table);
+        assert!(length(&table) == 100, 0);
+        assert!(min(&table) == Option::some(u64_key(0)), 0);
+        assert!(max(&table) == Option::some(u64_key(99)), 0);
+        let key = min(&table);
+        i = 0;
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            let (val, _, next) = borrow_iter(&table, copy k);
+            assert!(key_to_u64(&k) == i && *val == i, 0);
+            key = next;
+            i = i + 1;
+        };
+        assert!(i == 100, 0);
+        i = 0;
+        while (i < 100) {
+            assert!(remove(&mut table, u64_key(i)) == i, 0);
+            i = i + 3;
+        };
+        check_invariants(&table);
+        assert!(!contains(&table, u64_key(3)) && contains(&table, u64_key(4)), 0);
+        assert!(lower_bound(&table, u64_key(3)) == Option::some(u64_key(4)), 0);
+        assert!(lower_bound(&table, u64_key(4)) == Option::some(u64_key(4)), 0);
+        assert!(upper_bound(&table, u64_key(4)) == Option::some(u64_key(5)), 0);
+        assert!(upper_bound(&table, u64_key(98)) == Option::none(), 0);
+        destroy_table(table);
+    }
+
+    #[test]
+    fun range_test() {
+        let table = new();
+        let i = 0;
+        while (i < 20) {
+            add(&mut table, u64_key(i * 10), i);
+            i = i + 1;
+        };
+        let (keys, next) = range(&table, u64_key(25), u64_key(75), 3);
+        assert!(keys == vector[u64_key(30), u64_key(40), u64_key(50)], 0);
+        assert!(next == Option::some(u64_key(60)), 0);
+        let (keys, next) = range(&table, *Option::borrow(&next), u64_key(75), 3);
+        assert!(keys == vector[u64_key(60), u64_key(70)], 0);
+        assert!(Option::is_none(&next), 0);
+        let (keys, _) = range(&table, u64_key(500), u64_key(600), 3);
+        assert!(Vector::is_empty(&keys), 0);
+        destroy_table(table);
+    }
+
+    #[test]
+    fun string_key_test() {
+        let table = new();
+        add(&mut table, string_key(&ASCII::string(b"balcony")), 2);
+        add(&mut table, string_key(&ASCII::string(b"box")), 3);
+        add(&mut table, string_key(&ASCII::string(b"b")), 1);
+        add(&mut table, string_key(&ASCII::string(b"floor")), 4);
+        check_invariants(&table);
+        assert!(min(&table) == Option::some(b"b"), 0);
+        assert!(max(&table) == Option::some(b"floor"), 0);
+        assert!(lower_bound(&table, b"bb") == Option::some(b"box"), 0);
+        let (keys, _) = range(&table, b"ba", b"c", 10);
+        assert!(keys == vector[b"balcony", b"box"], 0);
+        destroy_table(table);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 2)]
+    fun add_duplicate_test() {
+        let table = new();
+        add(&mut table, u64_key(1), 1);
+        add(&mut table, u64_key(1), 1);
+        destroy_table(table);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 3)]
+    fun borrow_missing_key_test() {
+        let table = new<u64>();
+        borrow(&table, u64_key(1));
+        destroy_empty(table);
+    }
+}
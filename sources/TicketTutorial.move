@@ -2,31 +2,71 @@ module TicketTutorial::Tickets {
     use Std::Signer;
 	use Std::ASCII;
 	use Std::Vector;
-    use AptosFramework::TestCoin::TestCoin;
+	use Std::Option::{Self, Option};
 	use AptosFramework::Coin;
-	use AptosFramework::Table::{Self, Table};
+	use AptosFramework::TypeInfo::{Self, TypeInfo};
+	use TicketTutorial::MapTable::{Self, MapTable};
+	use TicketTutorial::MapSet::{Self, MapSet};
+
+	#[test_only]
+    use AptosFramework::TestCoin::TestCoin;
+	#[test_only]
     use AptosFramework::ManagedCoin;
 
 	struct SeatIdentifier has store, copy, drop {
+		section: ASCII::String,
 		row: ASCII::String,
 		seat_number: u64
 	}
 
 	struct ConcertTicket has key, store, drop {
+		event_id: u64,
 		identifier: SeatIdentifier,
 		ticket_code: ASCII::String,
+		tier_id: u64,
+		// set by update_ticket_price, takes precedence over the tier price
+		price_override: Option<u64>,
+		// what the buyer paid, stamped at purchase; unsold tickets are priced by their tier
+		price_paid: u64
+	}
+
+	// a price shared by many seats of one event, so repricing the tier reprices every unsold seat in it
+	struct PriceTier has store, drop {
+		name: ASCII::String,
 		price: u64
 	}
 
+	struct Event has store {
+		id: u64,
+		name: ASCII::String,
+		start_time: u64,
+		available_tickets: MapTable<SeatIdentifier, ConcertTicket>,
+		tiers: MapTable<u64, PriceTier>,
+		next_tier_id: u64
+	}
+
 	struct Venue has key {
-		available_tickets: Table<SeatIdentifier, ConcertTicket>,
-		max_seats: u64
+		events: MapTable<u64, Event>,
+		// section names (e.g. Floor, Balcony, Box), shared by all events at the venue
+		sections: MapSet<ASCII::String>,
+		next_event_id: u64,
+		// the most tickets any single event can offer
+		max_seats: u64,
+		// the only coin type tickets at this venue can be bought with, fixed by init_venue
+		coin_type: TypeInfo,
+		// one of VENUE_OPEN, VENUE_PAUSED or VENUE_CLOSED
+		status: u8
 	}
 	
 	struct TicketEnvelope has key {
 		tickets: vector<ConcertTicket>
 	}
 
+	// module-wide kill switch held at @TicketTutorial; while paused no venue can sell tickets
+	struct SalesSwitch has key {
+		paused: bool
+	}
+
 	const ENO_VENUE: u64 = 0;
 	const ENO_TICKETS: u64 = 1;
 	const ENO_ENVELOPE: u64 = 2;
@@ -35,55 +75,314 @@ module TicketTutorial::Tickets {
 	const EINVALID_PRICE: u64 = 5;
 	const EMAX_SEATS: u64 = 6;
 	const EINVALID_BALANCE: u64 = 7;
+	const ECOIN_TYPE_MISMATCH: u64 = 8;
+	const ENO_EVENT: u64 = 9;
+	const EMISMATCHED_LENGTHS: u64 = 10;
+	const ESEAT_EXISTS: u64 = 11;
+	const ENO_SECTION: u64 = 12;
+	const ESECTION_EXISTS: u64 = 13;
+	const ENO_TIER: u64 = 14;
+	const ENO_SEAT_TO_UPDATE: u64 = 15;
+	const ENO_SEAT_TO_WITHDRAW: u64 = 16;
+	const ENO_SEAT_TO_DELETE: u64 = 17;
+	const EVENUE_CLOSED: u64 = 18;
+	const ESALES_PAUSED: u64 = 19;
+	const EINVALID_STATUS: u64 = 20;
+	const EVENUE_NOT_EMPTY: u64 = 21;
+	const EMAX_SEATS_TOO_LOW: u64 = 22;
+	const ENOT_PUBLISHER: u64 = 23;
+	const EINVALID_SEAT_RANGE: u64 = 24;
 
-	public(script) fun init_venue(venue_owner: &signer, max_seats: u64) {
-		let available_tickets = Table::new<SeatIdentifier, ConcertTicket>();
-		move_to<Venue>(venue_owner, Venue {available_tickets, max_seats})
+	// tickets can be created and sold
+	const VENUE_OPEN: u8 = 0;
+	// tickets can be created but not sold
+	const VENUE_PAUSED: u8 = 1;
+	// tickets can be neither created nor sold
+	const VENUE_CLOSED: u8 = 2;
+
+	public(script) fun init_venue<CoinType>(venue_owner: &signer, max_seats: u64) {
+		let events = MapTable::new<u64, Event>();
+		let coin_type = TypeInfo::type_of<CoinType>();
+		move_to<Venue>(venue_owner, Venue {events, sections: MapSet::new(), next_event_id: 0, max_seats, coin_type, status: VENUE_OPEN})
 	}
 
-	public(script) fun create_ticket(venue_owner: &signer, row: vector<u8>, seat_number: u64, ticket_code: vector<u8>, price: u64) acquires Venue {
+	// the new limit must still fit the tickets already on sale for every event
+	public(script) fun set_max_seats(venue_owner: &signer, max_seats: u64) acquires Venue {
 		let venue_owner_addr = Signer::address_of(venue_owner);
 		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
-		let current_seat_count = available_ticket_count(venue_owner_addr);
 		let venue = borrow_global_mut<Venue>(venue_owner_addr);
-		assert!(current_seat_count < venue.max_seats, EMAX_SEATS);
-		let identifier = SeatIdentifier { row: ASCII::string(row), seat_number };
-		let ticket = ConcertTicket { identifier, ticket_code: (ASCII::string(ticket_code)), price};
-		Table::add(&mut venue.available_tickets, &identifier, ticket)
+		let event_id = MapTable::head_key(&venue.events);
+		while (Option::is_some(&event_id)) {
+			let (event, _, next) = MapTable::borrow_iter(&venue.events, *Option::borrow(&event_id));
+			assert!(MapTable::length(&event.available_tickets) <= max_seats, EMAX_SEATS_TOO_LOW);
+			event_id = next;
+		};
+		venue.max_seats = max_seats;
+	}
+
+	// a closed venue cannot be reopened; the only way on from VENUE_CLOSED is close_venue
+	public(script) fun set_venue_status(venue_owner: &signer, status: u8) acquires Venue {
+		assert!(status == VENUE_OPEN || status == VENUE_PAUSED || status == VENUE_CLOSED, EINVALID_STATUS);
+		set_status(venue_owner, status)
+	}
+
+	// stops purchases without touching inventory, e.g. when a price was mis-set
+	public(script) fun pause_sales(venue_owner: &signer) acquires Venue {
+		set_status(venue_owner, VENUE_PAUSED)
+	}
+
+	public(script) fun resume_sales(venue_owner: &signer) acquires Venue {
+		set_status(venue_owner, VENUE_OPEN)
+	}
+
+	// stops purchases at every venue; only the account that published this module can flip the switch
+	public(script) fun pause_all_sales(publisher: &signer) acquires SalesSwitch {
+		set_global_pause(publisher, true)
+	}
+
+	public(script) fun resume_all_sales(publisher: &signer) acquires SalesSwitch {
+		set_global_pause(publisher, false)
+	}
+
+	// true whenever purchase_ticket would refuse a sale for the venue's status: paused, closed or paused globally
+	public fun sales_paused(venue_owner_addr: address): bool acquires Venue, SalesSwitch {
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		borrow_global<Venue>(venue_owner_addr).status != VENUE_OPEN || globally_paused()
+	}
+
+	// removes the venue and all of its events once no event has tickets left on sale; sold tickets stay with their buyers
+	public(script) fun close_venue(venue_owner: &signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let Venue { events, sections, next_event_id: _, max_seats: _, coin_type: _, status: _ } = move_from<Venue>(venue_owner_addr);
+		while (!MapTable::empty(&events)) {
+			let (_, event) = MapTable::pop_front(&mut events);
+			let Event { id: _, name: _, start_time: _, available_tickets, tiers, next_tier_id: _ } = event;
+			assert!(MapTable::empty(&available_tickets), EVENUE_NOT_EMPTY);
+			MapTable::destroy_empty(available_tickets);
+			MapTable::destroy(tiers);
+		};
+		MapTable::destroy_empty(events);
+		MapSet::destroy(sections);
+	}
+
+	public(script) fun create_section(venue_owner: &signer, name: vector<u8>) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let venue = borrow_global_mut<Venue>(venue_owner_addr);
+		let name = ASCII::string(name);
+		assert!(!MapSet::contains(&venue.sections, name), ESECTION_EXISTS);
+		MapSet::add(&mut venue.sections, name)
+	}
+
+	// events are numbered from 0 in creation order
+	public(script) fun create_event(venue_owner: &signer, name: vector<u8>, start_time: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let venue = borrow_global_mut<Venue>(venue_owner_addr);
+		let id = venue.next_event_id;
+		venue.next_event_id = id + 1;
+		let event = Event {
+			id,
+			name: ASCII::string(name),
+			start_time,
+			available_tickets: MapTable::new(),
+			tiers: MapTable::new(),
+			next_tier_id: 0
+		};
+		MapTable::add(&mut venue.events, id, event)
+	}
+
+	// tiers are numbered from 0 in creation order within each event
+	public(script) fun create_price_tier(venue_owner: &signer, event_id: u64, name: vector<u8>, price: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event_mut(borrow_global_mut<Venue>(venue_owner_addr), event_id);
+		let id = event.next_tier_id;
+		event.next_tier_id = id + 1;
+		MapTable::add(&mut event.tiers, id, PriceTier { name: ASCII::string(name), price })
+	}
+
+	// reprices every unsold seat in the tier; tickets already sold keep the price they were bought at
+	public(script) fun set_tier_price(venue_owner: &signer, event_id: u64, tier_id: u64, price: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event_mut(borrow_global_mut<Venue>(venue_owner_addr), event_id);
+		assert!(MapTable::contains(&event.tiers, tier_id), ENO_TIER);
+		MapTable::borrow_mut(&mut event.tiers, tier_id).price = price;
+	}
+
+	public(script) fun create_ticket(venue_owner: &signer, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64, ticket_code: vector<u8>, tier_id: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		assert_section_exists(venue_owner_addr, section);
+		let venue = borrow_global_mut<Venue>(venue_owner_addr);
+		let event = borrow_event_with_room(venue, event_id, 1);
+		add_ticket(event, section, row, seat_number, ticket_code, tier_id)
     }
 
-	public(script) fun available_ticket_count(venue_owner_addr: address): u64 acquires Venue {
+	// creates seats first_seat..=last_seat in one row, with ticket codes made of the section, row and seat number (e.g. "FLOOR-A24")
+	public(script) fun create_tickets_range(venue_owner: &signer, event_id: u64, section: vector<u8>, row: vector<u8>, first_seat: u64, last_seat: u64, tier_id: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		assert!(first_seat <= last_seat, EINVALID_SEAT_RANGE);
+		assert_section_exists(venue_owner_addr, section);
+		let venue = borrow_global_mut<Venue>(venue_owner_addr);
+		let event = borrow_event_with_room(venue, event_id, last_seat - first_seat + 1);
+		let seat_number = first_seat;
+		loop {
+			let ticket_code = copy section;
+			Vector::push_back(&mut ticket_code, 45);
+			Vector::append(&mut ticket_code, copy row);
+			Vector::append(&mut ticket_code, u64_to_ascii(seat_number));
+			add_ticket(event, copy section, copy row, seat_number, ticket_code, tier_id);
+			// stop before incrementing so last_seat == MAX_U64 does not overflow
+			if (seat_number == last_seat) break;
+			seat_number = seat_number + 1;
+		};
+	}
+
+	// creates one ticket per index of the parallel vectors, which must all have the same length
+	public(script) fun create_tickets(venue_owner: &signer, event_id: u64, sections: vector<vector<u8>>, rows: vector<vector<u8>>, seat_numbers: vector<u64>, codes: vector<vector<u8>>, tier_ids: vector<u64>) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let count = Vector::length(&sections);
+		assert!(Vector::length(&rows) == count, EMISMATCHED_LENGTHS);
+		assert!(Vector::length(&seat_numbers) == count, EMISMATCHED_LENGTHS);
+		assert!(Vector::length(&codes) == count, EMISMATCHED_LENGTHS);
+		assert!(Vector::length(&tier_ids) == count, EMISMATCHED_LENGTHS);
+		let i = 0;
+		while (i < count) {
+			assert_section_exists(venue_owner_addr, *Vector::borrow(&sections, i));
+			i = i + 1;
+		};
+		let venue = borrow_global_mut<Venue>(venue_owner_addr);
+		let event = borrow_event_with_room(venue, event_id, count);
+		let i = 0;
+		while (i < count) {
+			add_ticket(event, *Vector::borrow(&sections, i), *Vector::borrow(&rows, i), *Vector::borrow(&seat_numbers, i), *Vector::borrow(&codes, i), *Vector::borrow(&tier_ids, i));
+			i = i + 1;
+		};
+	}
+
+	// gives one seat its own price, which later tier price changes no longer affect
+	public(script) fun update_ticket_price(venue_owner: &signer, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64, price: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event_mut(borrow_global_mut<Venue>(venue_owner_addr), event_id);
+		let seat = seat_identifier(section, row, seat_number);
+		assert!(MapTable::contains(&event.available_tickets, seat), ENO_SEAT_TO_UPDATE);
+		MapTable::borrow_mut(&mut event.available_tickets, seat).price_override = Option::some(price);
+	}
+
+	// pulls a seat from sale into the owner's own envelope, e.g. to hold it for a guest
+	public(script) fun withdraw_ticket(venue_owner: &signer, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64) acquires Venue, TicketEnvelope {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event_mut(borrow_global_mut<Venue>(venue_owner_addr), event_id);
+		let seat = seat_identifier(section, row, seat_number);
+		assert!(MapTable::contains(&event.available_tickets, seat), ENO_SEAT_TO_WITHDRAW);
+		let ticket = MapTable::remove(&mut event.available_tickets, seat);
+		if (!exists<TicketEnvelope>(venue_owner_addr)) {
+			move_to<TicketEnvelope>(venue_owner, TicketEnvelope {tickets: Vector::empty<ConcertTicket>()});
+		};
+		let envelope = borrow_global_mut<TicketEnvelope>(venue_owner_addr);
+		Vector::push_back<ConcertTicket>(&mut envelope.tickets, ticket);
+	}
+
+	// removes a seat created by mistake; it can be created again afterwards
+	public(script) fun delete_ticket(venue_owner: &signer, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event_mut(borrow_global_mut<Venue>(venue_owner_addr), event_id);
+		let seat = seat_identifier(section, row, seat_number);
+		assert!(MapTable::contains(&event.available_tickets, seat), ENO_SEAT_TO_DELETE);
+		MapTable::remove(&mut event.available_tickets, seat);
+	}
+
+	public(script) fun available_ticket_count(venue_owner_addr: address, event_id: u64): u64 acquires Venue {
 		let venue = borrow_global<Venue>(venue_owner_addr);
-		Table::length<SeatIdentifier, ConcertTicket>(&venue.available_tickets)
-	}
-
-	// fun get_ticket_info(venue_owner_addr: address, seat:vector<u8>): (bool, vector<u8>, u64, u64) acquires Venue {
-	// 	assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
-	// 	let venue = borrow_global<Venue>(venue_owner_addr);
-	// 	let i = 0;
-    //     let len = Vector::length<ConcertTicket>(&venue.available_tickets);
-    //     while (i < len) {
-	// 		let ticket= Vector::borrow<ConcertTicket>(&venue.available_tickets, i);
-	// 		if (ticket.seat == seat) return (true, ticket.ticket_code, ticket.price, i);
-    //         i = i + 1;
-    //     };
-	//     return (false, b"", 0, 0)
-	// }
-
-	// public(script) fun get_ticket_price(venue_owner_addr: address, seat:vector<u8>): (bool, u64) acquires Venue {
-	// 	let (success, _, price, _) = get_ticket_info(venue_owner_addr, seat);
-	// 	assert!(success, EINVALID_TICKET);
-	// 	return (success, price)
-	// }
-
-	public(script) fun purchase_ticket(buyer: &signer, venue_owner_addr: address, row: vector<u8>, seat_number: u64) acquires Venue, TicketEnvelope {	
+		let event = borrow_event(venue, event_id);
+		MapTable::length<SeatIdentifier, ConcertTicket>(&event.available_tickets)
+	}
+
+	public fun event_ids(venue_owner_addr: address): vector<u64> acquires Venue {
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		MapTable::keys(&borrow_global<Venue>(venue_owner_addr).events)
+	}
+
+	public fun get_event_info(venue_owner_addr: address, event_id: u64): (ASCII::String, u64) acquires Venue {
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event(borrow_global<Venue>(venue_owner_addr), event_id);
+		(event.name, event.start_time)
+	}
+
+	public fun get_tier_info(venue_owner_addr: address, event_id: u64, tier_id: u64): (ASCII::String, u64) acquires Venue {
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event(borrow_global<Venue>(venue_owner_addr), event_id);
+		assert!(MapTable::contains(&event.tiers, tier_id), ENO_TIER);
+		let tier = MapTable::borrow(&event.tiers, tier_id);
+		(tier.name, tier.price)
+	}
+
+	public fun seat_identifier(section: vector<u8>, row: vector<u8>, seat_number: u64): SeatIdentifier {
+		SeatIdentifier { section: ASCII::string(section), row: ASCII::string(row), seat_number }
+	}
+
+	public fun seat_section(seat: &SeatIdentifier): ASCII::String {
+		seat.section
+	}
+
+	public fun seat_row(seat: &SeatIdentifier): ASCII::String {
+		seat.row
+	}
+
+	public fun seat_number(seat: &SeatIdentifier): u64 {
+		seat.seat_number
+	}
+
+	// lists up to `limit` available seats for an event in creation order, starting at `start` (or the first seat
+	// when none), and returns the seat to pass as `start` for the next page (none once every seat has been listed)
+	public fun available_seats(venue_owner_addr: address, event_id: u64, start: Option<SeatIdentifier>, limit: u64): (vector<SeatIdentifier>, Option<SeatIdentifier>) acquires Venue {
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event(borrow_global<Venue>(venue_owner_addr), event_id);
+		if (Option::is_some(&start) && !MapTable::contains(&event.available_tickets, *Option::borrow(&start))) {
+			abort EINVALID_TICKET
+		};
+		MapTable::page(&event.available_tickets, start, limit)
+	}
+
+	// the price is the seat's own price if one was set, otherwise the current price of its tier
+	public fun get_ticket_info(venue_owner_addr: address, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64): (bool, ASCII::String, u64) acquires Venue {
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let event = borrow_event(borrow_global<Venue>(venue_owner_addr), event_id);
+		let seat = seat_identifier(section, row, seat_number);
+		if (!MapTable::contains(&event.available_tickets, seat)) return (false, ASCII::string(b""), 0);
+		let ticket = MapTable::borrow(&event.available_tickets, seat);
+		(true, ticket.ticket_code, ticket_price(event, ticket))
+	}
+
+	public fun get_ticket_price(venue_owner_addr: address, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64): u64 acquires Venue {
+		let (success, _, price) = get_ticket_info(venue_owner_addr, event_id, section, row, seat_number);
+		assert!(success, EINVALID_TICKET);
+		price
+	}
+
+	public(script) fun purchase_ticket<CoinType>(buyer: &signer, venue_owner_addr: address, event_id: u64, section: vector<u8>, row: vector<u8>, seat_number: u64) acquires Venue, TicketEnvelope, SalesSwitch {	
 		let buyer_addr = Signer::address_of(buyer);	
-		let target_seat_id = SeatIdentifier { row: ASCII::string(row), seat_number };
+		let target_seat_id = seat_identifier(section, row, seat_number);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
 		let venue = borrow_global_mut<Venue>(venue_owner_addr);	
-		assert!(Table::contains<SeatIdentifier, ConcertTicket>(&venue.available_tickets, &target_seat_id), EINVALID_TICKET);
-		let target_ticket = Table::borrow<SeatIdentifier, ConcertTicket>(&venue.available_tickets, &target_seat_id);
-		Coin::transfer<TestCoin>(buyer, venue_owner_addr, target_ticket.price);
-		let ticket = Table::remove<SeatIdentifier, ConcertTicket>(&mut venue.available_tickets, &target_seat_id);
+		assert!(venue.status != VENUE_CLOSED, EVENUE_CLOSED);
+		assert!(venue.status != VENUE_PAUSED && !globally_paused(), ESALES_PAUSED);
+		assert!(venue.coin_type == TypeInfo::type_of<CoinType>(), ECOIN_TYPE_MISMATCH);
+		let event = borrow_event_mut(venue, event_id);
+		assert!(MapTable::contains<SeatIdentifier, ConcertTicket>(&event.available_tickets, target_seat_id), EINVALID_TICKET);
+		let target_ticket = MapTable::borrow<SeatIdentifier, ConcertTicket>(&event.available_tickets, target_seat_id);
+		let price = ticket_price(event, target_ticket);
+		Coin::transfer<CoinType>(buyer, venue_owner_addr, price);
+		let ticket = MapTable::remove<SeatIdentifier, ConcertTicket>(&mut event.available_tickets, target_seat_id);
+		ticket.price_paid = price;
 		if (!exists<TicketEnvelope>(buyer_addr)) {
 			move_to<TicketEnvelope>(buyer, TicketEnvelope {tickets: Vector::empty<ConcertTicket>()});
 		};	
@@ -91,22 +390,100 @@ module TicketTutorial::Tickets {
 		Vector::push_back<ConcertTicket>(&mut envelope.tickets, ticket);
 	}
 
+	// borrows the event, checking once that the venue is not closed and the event can take `count` more tickets
+	fun borrow_event_with_room(venue: &mut Venue, event_id: u64, count: u64): &mut Event {
+		assert!(venue.status != VENUE_CLOSED, EVENUE_CLOSED);
+		let max_seats = venue.max_seats;
+		let event = borrow_event_mut(venue, event_id);
+		assert!(count <= max_seats - MapTable::length(&event.available_tickets), EMAX_SEATS);
+		event
+	}
+
+	// any seat that already exists aborts the whole transaction, so a batch is created entirely or not at all
+	fun add_ticket(event: &mut Event, section: vector<u8>, row: vector<u8>, seat_number: u64, ticket_code: vector<u8>, tier_id: u64) {
+		let identifier = seat_identifier(section, row, seat_number);
+		assert!(!MapTable::contains(&event.available_tickets, identifier), ESEAT_EXISTS);
+		assert!(MapTable::contains(&event.tiers, tier_id), ENO_TIER);
+		let ticket = ConcertTicket { event_id: event.id, identifier, ticket_code: (ASCII::string(ticket_code)), tier_id, price_override: Option::none(), price_paid: 0 };
+		MapTable::add(&mut event.available_tickets, identifier, ticket)
+	}
+
+	// shared by set_venue_status and pause_sales/resume_sales so they follow the same rules
+	fun set_status(venue_owner: &signer, status: u8) acquires Venue {
+		let venue_owner_addr = Signer::address_of(venue_owner);
+		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		let venue = borrow_global_mut<Venue>(venue_owner_addr);
+		assert!(venue.status != VENUE_CLOSED, EVENUE_CLOSED);
+		venue.status = status;
+	}
+
+	fun set_global_pause(publisher: &signer, paused: bool) acquires SalesSwitch {
+		assert!(Signer::address_of(publisher) == @TicketTutorial, ENOT_PUBLISHER);
+		if (!exists<SalesSwitch>(@TicketTutorial)) {
+			move_to<SalesSwitch>(publisher, SalesSwitch { paused });
+		} else {
+			borrow_global_mut<SalesSwitch>(@TicketTutorial).paused = paused;
+		}
+	}
+
+	fun globally_paused(): bool acquires SalesSwitch {
+		exists<SalesSwitch>(@TicketTutorial) && borrow_global<SalesSwitch>(@TicketTutorial).paused
+	}
+
+	fun assert_section_exists(venue_owner_addr: address, section: vector<u8>) acquires Venue {
+		let venue = borrow_global<Venue>(venue_owner_addr);
+		assert!(MapSet::contains(&venue.sections, ASCII::string(section)), ENO_SECTION);
+	}
+
+	fun ticket_price(event: &Event, ticket: &ConcertTicket): u64 {
+		if (Option::is_some(&ticket.price_override)) return *Option::borrow(&ticket.price_override);
+		MapTable::borrow(&event.tiers, ticket.tier_id).price
+	}
+
+	fun u64_to_ascii(n: u64): vector<u8> {
+		let digits = Vector::empty<u8>();
+		loop {
+			Vector::push_back(&mut digits, ((n % 10) as u8) + 48);
+			n = n / 10;
+			if (n == 0) break;
+		};
+		Vector::reverse(&mut digits);
+		digits
+	}
+
+	fun borrow_event(venue: &Venue, event_id: u64): &Event {
+		assert!(MapTable::contains(&venue.events, event_id), ENO_EVENT);
+		MapTable::borrow(&venue.events, event_id)
+	}
+
+	fun borrow_event_mut(venue: &mut Venue, event_id: u64): &mut Event {
+		assert!(MapTable::contains(&venue.events, event_id), ENO_EVENT);
+		MapTable::borrow_mut(&mut venue.events, event_id)
+	}
+
 	#[test(venue_owner = @0x3, buyer = @0x2, faucet = @0x1)]
-    public(script) fun sender_can_buy_ticket(venue_owner: signer, buyer: signer, faucet: signer) acquires Venue, TicketEnvelope {
+    public(script) fun sender_can_buy_ticket(venue_owner: signer, buyer: signer, faucet: signer) acquires Venue, TicketEnvelope, SalesSwitch {
 		
 		let venue_owner_addr = Signer::address_of(&venue_owner);
 
 		// initialize the venue
-		init_venue(&venue_owner, 3);
+		init_venue<TestCoin>(&venue_owner, 3);
 		assert!(exists<Venue>(venue_owner_addr), ENO_VENUE);
+		create_section(&venue_owner, b"FLOOR");
+
+		// schedule an event with two price tiers
+		create_event(&venue_owner, b"Opening Night", 1000);
+		assert!(event_ids(venue_owner_addr) == vector[0], ENO_EVENT);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_price_tier(&venue_owner, 0, b"Premium", 20);
 
 		// create some tickets
-		create_ticket(&venue_owner, b"A", 24, b"AB43C7F", 15);
-		create_ticket(&venue_owner, b"A", 25, b"AB43CFD", 15);
-		create_ticket(&venue_owner, b"A", 26, b"AB13C7F", 20);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 24, b"AB43C7F", 0);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 25, b"AB43CFD", 0);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 26, b"AB13C7F", 1);
 
 		// verify we have 3 tickets now
-		assert!(available_ticket_count(venue_owner_addr)==3, EINVALID_TICKET_COUNT);
+		assert!(available_ticket_count(venue_owner_addr, 0)==3, EINVALID_TICKET_COUNT);
 
 
 		// initialize & fund account to buy tickets
@@ -123,16 +500,439 @@ module TicketTutorial::Tickets {
         assert!(Coin::balance<TestCoin>(buyer_addr) == 100, EINVALID_BALANCE);
 
 		// // buy a ticket and confirm account balance changes
-		purchase_ticket(&buyer, venue_owner_addr, b"A", 24);
+		purchase_ticket<TestCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 24);
 		assert!(exists<TicketEnvelope>(buyer_addr), ENO_ENVELOPE);
         assert!(Coin::balance<TestCoin>(buyer_addr) == 85, EINVALID_BALANCE);
 		assert!(Coin::balance<TestCoin>(venue_owner_addr) == 15, EINVALID_BALANCE);
-	    assert!(available_ticket_count(venue_owner_addr)==2, EINVALID_TICKET_COUNT);
+	    assert!(available_ticket_count(venue_owner_addr, 0)==2, EINVALID_TICKET_COUNT);
 
 		// buy a second ticket & ensure balance has changed by 20
-		purchase_ticket(&buyer, venue_owner_addr, b"A", 26);
+		purchase_ticket<TestCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 26);
 		assert!(Coin::balance<TestCoin>(buyer_addr) == 65, EINVALID_BALANCE);
 		assert!(Coin::balance<TestCoin>(venue_owner_addr) == 35, EINVALID_BALANCE);
+
+		// both tickets record the event they were bought for and what was paid
+		let envelope = borrow_global<TicketEnvelope>(buyer_addr);
+		assert!(Vector::length(&envelope.tickets) == 2, EINVALID_TICKET_COUNT);
+		assert!(Vector::borrow(&envelope.tickets, 1).event_id == 0, EINVALID_TICKET);
+		assert!(Vector::borrow(&envelope.tickets, 1).price_paid == 20, EINVALID_PRICE);
 		
     }
-}
\ No newline at end of file
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun venue_lists_available_seats(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 5);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Matinee", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 10);
+		create_price_tier(&venue_owner, 0, b"Premium", 30);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"B", 2, b"BB00002", 1);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"AA00001", 0);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 2, b"AA00002", 0);
+
+		// seats come back in creation order, two per page
+		let (seats, next) = available_seats(venue_owner_addr, 0, Option::none(), 2);
+		assert!(Vector::length(&seats) == 2, EINVALID_TICKET_COUNT);
+		assert!(*Vector::borrow(&seats, 0) == seat_identifier(b"FLOOR", b"B", 2), EINVALID_TICKET);
+		assert!(*Vector::borrow(&seats, 1) == seat_identifier(b"FLOOR", b"A", 1), EINVALID_TICKET);
+		assert!(next == Option::some(seat_identifier(b"FLOOR", b"A", 2)), EINVALID_TICKET);
+		let (seats, next) = available_seats(venue_owner_addr, 0, next, 2);
+		assert!(seats == vector[seat_identifier(b"FLOOR", b"A", 2)], EINVALID_TICKET);
+		assert!(Option::is_none(&next), EINVALID_TICKET);
+
+		// lookups by seat
+		let (success, code, price) = get_ticket_info(venue_owner_addr, 0, b"FLOOR", b"A", 1);
+		assert!(success && code == ASCII::string(b"AA00001") && price == 10, EINVALID_TICKET);
+		let (success, _, _) = get_ticket_info(venue_owner_addr, 0, b"FLOOR", b"C", 1);
+		assert!(!success, EINVALID_TICKET);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"B", 2) == 30, EINVALID_PRICE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 4)]
+	public(script) fun price_of_missing_seat_aborts(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 1);
+		create_event(&venue_owner, b"Matinee", 1000);
+		get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 1);
+	}
+
+	#[test_only]
+	struct VenueCoin {}
+
+	#[test(venue_owner = @0x3, buyer = @0x2, issuer = @TicketTutorial)]
+	public(script) fun sender_can_buy_ticket_with_custom_coin(venue_owner: signer, buyer: signer, issuer: signer) acquires Venue, TicketEnvelope, SalesSwitch {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		let buyer_addr = Signer::address_of(&buyer);
+		init_venue<VenueCoin>(&venue_owner, 2);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Matinee", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 40);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"AA00001", 0);
+
+		// issue the venue's own currency and fund the buyer
+		ManagedCoin::initialize<VenueCoin>(&issuer, b"VenueCoin", b"VEN", 6, false);
+		ManagedCoin::register<VenueCoin>(&venue_owner);
+		ManagedCoin::register<VenueCoin>(&buyer);
+		ManagedCoin::mint<VenueCoin>(&issuer, buyer_addr, 100);
+
+		purchase_ticket<VenueCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 1);
+		assert!(Coin::balance<VenueCoin>(buyer_addr) == 60, EINVALID_BALANCE);
+		assert!(Coin::balance<VenueCoin>(venue_owner_addr) == 40, EINVALID_BALANCE);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 0, EINVALID_TICKET_COUNT);
+	}
+
+	#[test(venue_owner = @0x3, buyer = @0x2)]
+	#[expected_failure(abort_code = 8)]
+	public(script) fun purchase_with_wrong_coin_aborts(venue_owner: signer, buyer: signer) acquires Venue, TicketEnvelope, SalesSwitch {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<VenueCoin>(&venue_owner, 1);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Matinee", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 40);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"AA00001", 0);
+		purchase_ticket<TestCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 1);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun events_keep_separate_inventories(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 2);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_event(&venue_owner, b"Saturday Show", 2000);
+		assert!(event_ids(venue_owner_addr) == vector[0, 1], ENO_EVENT);
+		let (name, start_time) = get_event_info(venue_owner_addr, 1);
+		assert!(name == ASCII::string(b"Saturday Show") && start_time == 2000, ENO_EVENT);
+
+		// the same seat can be sold for each event at its own tier prices, and max_seats applies per event
+		create_price_tier(&venue_owner, 0, b"Standard", 10);
+		create_price_tier(&venue_owner, 1, b"Standard", 25);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"FRI0001", 0);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 2, b"FRI0002", 0);
+		create_ticket(&venue_owner, 1, b"FLOOR", b"A", 1, b"SAT0001", 0);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 2, EINVALID_TICKET_COUNT);
+		assert!(available_ticket_count(venue_owner_addr, 1) == 1, EINVALID_TICKET_COUNT);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 1) == 10, EINVALID_PRICE);
+		assert!(get_ticket_price(venue_owner_addr, 1, b"FLOOR", b"A", 1) == 25, EINVALID_PRICE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 9)]
+	public(script) fun create_ticket_for_missing_event_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 2);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_ticket(&venue_owner, 1, b"FLOOR", b"A", 1, b"SAT0001", 0);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun owner_can_create_tickets_in_bulk(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 15);
+		create_section(&venue_owner, b"FLOOR");
+		create_section(&venue_owner, b"BOX");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_price_tier(&venue_owner, 0, b"Box", 30);
+
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 12, 0);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 12, EINVALID_TICKET_COUNT);
+		let (success, code, price) = get_ticket_info(venue_owner_addr, 0, b"FLOOR", b"A", 12);
+		assert!(success && code == ASCII::string(b"FLOOR-A12") && price == 15, EINVALID_TICKET);
+
+		create_tickets(&venue_owner, 0, vector[b"FLOOR", b"FLOOR", b"BOX"], vector[b"B", b"B", b"A"], vector[1, 2, 1], vector[b"B-1", b"B-2", b"BOX-1"], vector[0, 0, 1]);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 15, EINVALID_TICKET_COUNT);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"BOX", b"A", 1) == 30, EINVALID_PRICE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 6)]
+	public(script) fun bulk_create_over_capacity_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 11, 0);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 11)]
+	public(script) fun bulk_create_existing_seat_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 3, b"A3", 0);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 5, 0);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun bulk_create_range_ending_at_max_seat(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 18446744073709551614, 18446744073709551615, 0);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 2, EINVALID_TICKET_COUNT);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 18446744073709551615) == 15, EINVALID_PRICE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 24)]
+	public(script) fun bulk_create_reversed_range_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 5, 1, 0);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 10)]
+	public(script) fun bulk_create_mismatched_lengths_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets(&venue_owner, 0, vector[b"FLOOR", b"FLOOR"], vector[b"B", b"B"], vector[1, 2], vector[b"B-1"], vector[0, 0]);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun tier_price_change_reprices_unsold_seats(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_section(&venue_owner, b"BALCONY");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_price_tier(&venue_owner, 0, b"Cheap Seats", 5);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 3, 0);
+		create_tickets_range(&venue_owner, 0, b"BALCONY", b"A", 1, 3, 1);
+
+		// the section is part of the seat identity, so row A seat 1 exists once per section
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 1) == 15, EINVALID_PRICE);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"BALCONY", b"A", 1) == 5, EINVALID_PRICE);
+
+		set_tier_price(&venue_owner, 0, 0, 18);
+		let (name, price) = get_tier_info(venue_owner_addr, 0, 0);
+		assert!(name == ASCII::string(b"Standard") && price == 18, EINVALID_PRICE);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 1) == 18, EINVALID_PRICE);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 3) == 18, EINVALID_PRICE);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"BALCONY", b"A", 1) == 5, EINVALID_PRICE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 12)]
+	public(script) fun create_ticket_in_missing_section_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_ticket(&venue_owner, 0, b"BOX", b"A", 1, b"BOX-A1", 0);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 14)]
+	public(script) fun create_ticket_with_missing_tier_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"FLOOR-A1", 1);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun owner_can_update_ticket_price(venue_owner: signer) acquires Venue {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 2, 0);
+
+		// the seat keeps its own price when the tier is repriced
+		update_ticket_price(&venue_owner, 0, b"FLOOR", b"A", 1, 50);
+		set_tier_price(&venue_owner, 0, 0, 20);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 1) == 50, EINVALID_PRICE);
+		assert!(get_ticket_price(venue_owner_addr, 0, b"FLOOR", b"A", 2) == 20, EINVALID_PRICE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun owner_can_withdraw_and_delete_tickets(venue_owner: signer) acquires Venue, TicketEnvelope {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 3, 0);
+
+		// a withdrawn seat is held in the owner's envelope
+		withdraw_ticket(&venue_owner, 0, b"FLOOR", b"A", 1);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 2, EINVALID_TICKET_COUNT);
+		let envelope = borrow_global<TicketEnvelope>(venue_owner_addr);
+		assert!(Vector::borrow(&envelope.tickets, 0).identifier == seat_identifier(b"FLOOR", b"A", 1), EINVALID_TICKET);
+
+		// a deleted seat is gone and can be created again
+		delete_ticket(&venue_owner, 0, b"FLOOR", b"A", 2);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 1, EINVALID_TICKET_COUNT);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 2, b"FLOOR-A2", 0);
+		assert!(available_ticket_count(venue_owner_addr, 0) == 2, EINVALID_TICKET_COUNT);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 15)]
+	public(script) fun update_price_of_missing_seat_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_event(&venue_owner, b"Friday Show", 1000);
+		update_ticket_price(&venue_owner, 0, b"FLOOR", b"A", 1, 50);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 16)]
+	public(script) fun withdraw_missing_seat_aborts(venue_owner: signer) acquires Venue, TicketEnvelope {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_event(&venue_owner, b"Friday Show", 1000);
+		withdraw_ticket(&venue_owner, 0, b"FLOOR", b"A", 1);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 17)]
+	public(script) fun delete_missing_seat_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 10);
+		create_event(&venue_owner, b"Friday Show", 1000);
+		delete_ticket(&venue_owner, 0, b"FLOOR", b"A", 1);
+	}
+
+	#[test(venue_owner = @0x3)]
+	public(script) fun owner_can_resize_and_close_venue(venue_owner: signer) acquires Venue, TicketEnvelope {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 2);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 2, 0);
+
+		// raise the limit, add a seat, then lower it back to what is on sale
+		set_max_seats(&venue_owner, 5);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 3, b"FLOOR-A3", 0);
+		set_max_seats(&venue_owner, 3);
+
+		// a paused venue can still take new tickets
+		set_venue_status(&venue_owner, VENUE_PAUSED);
+		delete_ticket(&venue_owner, 0, b"FLOOR", b"A", 3);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 3, b"FLOOR-A3", 0);
+
+		// once nothing is left on sale the venue can be removed
+		withdraw_ticket(&venue_owner, 0, b"FLOOR", b"A", 1);
+		delete_ticket(&venue_owner, 0, b"FLOOR", b"A", 2);
+		delete_ticket(&venue_owner, 0, b"FLOOR", b"A", 3);
+		close_venue(&venue_owner);
+		assert!(!exists<Venue>(venue_owner_addr), ENO_VENUE);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 22)]
+	public(script) fun max_seats_below_current_tickets_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 5);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 3, 0);
+		set_max_seats(&venue_owner, 2);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 21)]
+	public(script) fun close_venue_with_tickets_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 5);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"FLOOR-A1", 0);
+		close_venue(&venue_owner);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 18)]
+	public(script) fun create_ticket_at_closed_venue_aborts(venue_owner: signer) acquires Venue {
+		init_venue<TestCoin>(&venue_owner, 5);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		set_venue_status(&venue_owner, VENUE_CLOSED);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"FLOOR-A1", 0);
+	}
+
+	#[test(venue_owner = @0x3, buyer = @0x2)]
+	#[expected_failure(abort_code = 19)]
+	public(script) fun purchase_at_paused_venue_aborts(venue_owner: signer, buyer: signer) acquires Venue, TicketEnvelope, SalesSwitch {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 5);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"FLOOR-A1", 0);
+		set_venue_status(&venue_owner, VENUE_PAUSED);
+		purchase_ticket<TestCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 1);
+	}
+
+	#[test(venue_owner = @0x3, buyer = @0x2, issuer = @TicketTutorial)]
+	public(script) fun sales_can_be_paused_and_resumed(venue_owner: signer, buyer: signer, issuer: signer) acquires Venue, TicketEnvelope, SalesSwitch {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		let buyer_addr = Signer::address_of(&buyer);
+		init_venue<VenueCoin>(&venue_owner, 2);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Matinee", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 40);
+		create_tickets_range(&venue_owner, 0, b"FLOOR", b"A", 1, 2, 0);
+		ManagedCoin::initialize<VenueCoin>(&issuer, b"VenueCoin", b"VEN", 6, false);
+		ManagedCoin::register<VenueCoin>(&venue_owner);
+		ManagedCoin::register<VenueCoin>(&buyer);
+		ManagedCoin::mint<VenueCoin>(&issuer, buyer_addr, 100);
+
+		pause_sales(&venue_owner);
+		assert!(sales_paused(venue_owner_addr), EINVALID_STATUS);
+		resume_sales(&venue_owner);
+		purchase_ticket<VenueCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 1);
+
+		// the publisher's switch covers every venue until it is turned off again
+		pause_all_sales(&issuer);
+		assert!(sales_paused(venue_owner_addr), EINVALID_STATUS);
+		resume_all_sales(&issuer);
+		assert!(!sales_paused(venue_owner_addr), EINVALID_STATUS);
+		purchase_ticket<VenueCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 2);
+		assert!(Coin::balance<VenueCoin>(buyer_addr) == 20, EINVALID_BALANCE);
+	}
+
+	#[test(venue_owner = @0x3, buyer = @0x2, publisher = @TicketTutorial)]
+	#[expected_failure(abort_code = 19)]
+	public(script) fun purchase_while_globally_paused_aborts(venue_owner: signer, buyer: signer, publisher: signer) acquires Venue, TicketEnvelope, SalesSwitch {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 5);
+		create_section(&venue_owner, b"FLOOR");
+		create_event(&venue_owner, b"Friday Show", 1000);
+		create_price_tier(&venue_owner, 0, b"Standard", 15);
+		create_ticket(&venue_owner, 0, b"FLOOR", b"A", 1, b"FLOOR-A1", 0);
+		pause_all_sales(&publisher);
+		purchase_ticket<TestCoin>(&buyer, venue_owner_addr, 0, b"FLOOR", b"A", 1);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 23)]
+	public(script) fun only_publisher_can_pause_all_sales(venue_owner: signer) acquires SalesSwitch {
+		pause_all_sales(&venue_owner);
+	}
+
+	#[test(venue_owner = @0x3)]
+	#[expected_failure(abort_code = 18)]
+	public(script) fun closed_venue_cannot_reopen(venue_owner: signer) acquires Venue, SalesSwitch {
+		let venue_owner_addr = Signer::address_of(&venue_owner);
+		init_venue<TestCoin>(&venue_owner, 5);
+		set_venue_status(&venue_owner, VENUE_CLOSED);
+		assert!(sales_paused(venue_owner_addr), EINVALID_STATUS);
+		set_venue_status(&venue_owner, VENUE_OPEN);
+	}
+}